@@ -0,0 +1,244 @@
+use anyhow::{bail, Result};
+
+use crate::config::Config;
+
+/// Adapts `tf`'s subcommands to the executable and argument conventions of a
+/// specific terraform-compatible tool.
+pub trait Backend {
+    /// The executable to invoke, e.g. `terraform`, `tofu`, or `terragrunt`.
+    fn binary(&self) -> &str;
+
+    fn plan_args(&self, var_file: &str, out: Option<&str>) -> Vec<String>;
+    fn apply_args(&self, var_file: &str, auto_approve: bool) -> Vec<String>;
+    /// Apply a previously saved plan file directly, with no var-file or
+    /// interactive approval needed. Returns `None` when the backend can't
+    /// apply a single saved plan (e.g. Terragrunt's `run-all`, which fans
+    /// out over multiple modules).
+    fn apply_plan_args(&self, plan_file: &str) -> Option<Vec<String>>;
+    fn destroy_args(&self, var_file: &str) -> Vec<String>;
+    fn init_args(&self, backend_config: &str) -> Vec<String>;
+    fn workspace_list_args(&self) -> Vec<String>;
+    fn workspace_select_args(&self, name: &str) -> Vec<String>;
+    fn workspace_new_args(&self, name: &str) -> Vec<String>;
+    fn workspace_show_args(&self) -> Vec<String>;
+}
+
+pub struct Terraform {
+    bin: String,
+}
+
+impl Backend for Terraform {
+    fn binary(&self) -> &str {
+        &self.bin
+    }
+
+    fn plan_args(&self, var_file: &str, out: Option<&str>) -> Vec<String> {
+        let mut args = vec!["plan".into(), "-var-file".into(), var_file.to_string()];
+        if let Some(out) = out {
+            args.push("-out".into());
+            args.push(out.into());
+        }
+        args
+    }
+
+    fn apply_args(&self, var_file: &str, auto_approve: bool) -> Vec<String> {
+        let mut args = vec!["apply".into(), "-var-file".into(), var_file.to_string()];
+        if auto_approve {
+            args.push("--auto-approve".into());
+        }
+        args
+    }
+
+    fn apply_plan_args(&self, plan_file: &str) -> Option<Vec<String>> {
+        Some(vec!["apply".into(), plan_file.into()])
+    }
+
+    fn destroy_args(&self, var_file: &str) -> Vec<String> {
+        vec!["destroy".into(), "-var-file".into(), var_file.into()]
+    }
+
+    fn init_args(&self, backend_config: &str) -> Vec<String> {
+        vec![
+            "init".into(),
+            "-backend-config".into(),
+            backend_config.into(),
+        ]
+    }
+
+    fn workspace_list_args(&self) -> Vec<String> {
+        vec!["workspace".into(), "list".into()]
+    }
+
+    fn workspace_select_args(&self, name: &str) -> Vec<String> {
+        vec!["workspace".into(), "select".into(), name.into()]
+    }
+
+    fn workspace_new_args(&self, name: &str) -> Vec<String> {
+        vec!["workspace".into(), "new".into(), name.into()]
+    }
+
+    fn workspace_show_args(&self) -> Vec<String> {
+        vec!["workspace".into(), "show".into()]
+    }
+}
+
+/// OpenTofu is a drop-in fork of Terraform; only the executable name differs.
+pub struct OpenTofu {
+    inner: Terraform,
+}
+
+impl Backend for OpenTofu {
+    fn binary(&self) -> &str {
+        self.inner.binary()
+    }
+
+    fn plan_args(&self, var_file: &str, out: Option<&str>) -> Vec<String> {
+        self.inner.plan_args(var_file, out)
+    }
+
+    fn apply_args(&self, var_file: &str, auto_approve: bool) -> Vec<String> {
+        self.inner.apply_args(var_file, auto_approve)
+    }
+
+    fn apply_plan_args(&self, plan_file: &str) -> Option<Vec<String>> {
+        self.inner.apply_plan_args(plan_file)
+    }
+
+    fn destroy_args(&self, var_file: &str) -> Vec<String> {
+        self.inner.destroy_args(var_file)
+    }
+
+    fn init_args(&self, backend_config: &str) -> Vec<String> {
+        self.inner.init_args(backend_config)
+    }
+
+    fn workspace_list_args(&self) -> Vec<String> {
+        self.inner.workspace_list_args()
+    }
+
+    fn workspace_select_args(&self, name: &str) -> Vec<String> {
+        self.inner.workspace_select_args(name)
+    }
+
+    fn workspace_new_args(&self, name: &str) -> Vec<String> {
+        self.inner.workspace_new_args(name)
+    }
+
+    fn workspace_show_args(&self) -> Vec<String> {
+        self.inner.workspace_show_args()
+    }
+}
+
+/// Terragrunt wraps terraform/tofu; plan/apply/destroy/init fan out across
+/// every module via `run-all`, while workspace commands pass straight through.
+pub struct Terragrunt {
+    bin: String,
+}
+
+impl Backend for Terragrunt {
+    fn binary(&self) -> &str {
+        &self.bin
+    }
+
+    fn plan_args(&self, var_file: &str, out: Option<&str>) -> Vec<String> {
+        let mut args = vec![
+            "run-all".into(),
+            "plan".into(),
+            "-var-file".into(),
+            var_file.to_string(),
+        ];
+        if let Some(out) = out {
+            args.push("-out".into());
+            args.push(out.into());
+        }
+        args
+    }
+
+    fn apply_args(&self, var_file: &str, auto_approve: bool) -> Vec<String> {
+        let mut args = vec![
+            "run-all".into(),
+            "apply".into(),
+            "-var-file".into(),
+            var_file.to_string(),
+        ];
+        if auto_approve {
+            args.push("--auto-approve".into());
+        }
+        args
+    }
+
+    fn apply_plan_args(&self, _plan_file: &str) -> Option<Vec<String>> {
+        // `run-all apply` fans out over every module, so a single saved plan
+        // file can't be applied the way plain Terraform/OpenTofu can.
+        None
+    }
+
+    fn destroy_args(&self, var_file: &str) -> Vec<String> {
+        vec![
+            "run-all".into(),
+            "destroy".into(),
+            "-var-file".into(),
+            var_file.into(),
+        ]
+    }
+
+    fn init_args(&self, backend_config: &str) -> Vec<String> {
+        vec![
+            "run-all".into(),
+            "init".into(),
+            "-backend-config".into(),
+            backend_config.into(),
+        ]
+    }
+
+    fn workspace_list_args(&self) -> Vec<String> {
+        vec!["workspace".into(), "list".into()]
+    }
+
+    fn workspace_select_args(&self, name: &str) -> Vec<String> {
+        vec!["workspace".into(), "select".into(), name.into()]
+    }
+
+    fn workspace_new_args(&self, name: &str) -> Vec<String> {
+        vec!["workspace".into(), "new".into(), name.into()]
+    }
+
+    fn workspace_show_args(&self) -> Vec<String> {
+        vec!["workspace".into(), "show".into()]
+    }
+}
+
+/// Pick the active backend from (in order of precedence) an explicit
+/// `--backend` flag, the `backend` field in `tf.toml`, falling back to plain
+/// Terraform. The binary name can be further overridden via `terraform_bin`
+/// in config or the `TF_BINARY` env var.
+pub fn resolve(config: &Config, flag: Option<&str>) -> Result<Box<dyn Backend>> {
+    let kind = flag.unwrap_or(&config.backend);
+
+    Ok(match kind {
+        "terraform" => Box::new(Terraform {
+            bin: config
+                .terraform_bin
+                .clone()
+                .unwrap_or_else(|| "terraform".into()),
+        }),
+        "tofu" | "opentofu" => Box::new(OpenTofu {
+            inner: Terraform {
+                bin: config
+                    .terraform_bin
+                    .clone()
+                    .unwrap_or_else(|| "tofu".into()),
+            },
+        }),
+        "terragrunt" => Box::new(Terragrunt {
+            bin: config
+                .terraform_bin
+                .clone()
+                .unwrap_or_else(|| "terragrunt".into()),
+        }),
+        other => bail!(
+            "Unknown backend '{}' (expected terraform, tofu, or terragrunt)",
+            other
+        ),
+    })
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::{env, fs, path::Path, path::PathBuf};
+
+/// Resolved project configuration. Every field has a sensible default, so a
+/// project with no `tf.toml` at all behaves exactly like before this existed.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Template for the plan/apply/destroy var-file, e.g. `envs/{env}/main.tfvars`.
+    pub var_file_template: String,
+    /// Template for the `terraform init -backend-config` file.
+    pub backend_config_template: String,
+    /// Name of the environment variable that selects the active env (e.g. `ENV`).
+    pub env_var: String,
+    /// Environment variables that must be set before `plan`/`apply`/`destroy`/`init` run.
+    pub required_env: Vec<String>,
+    /// Which `Backend` to drive: `terraform`, `tofu`, or `terragrunt`.
+    pub backend: String,
+    /// Explicit override for the backend's executable path/name, if set.
+    pub terraform_bin: Option<String>,
+    /// Version constraint (e.g. `>= 1.5.0, < 2.0.0`) checked against the
+    /// installed backend before `plan`/`apply`/`init`. When `None`, a
+    /// `.terraform-version` file is used instead; with neither, the check is skipped.
+    pub required_version: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            var_file_template: "envs/{env}/main.tfvars".into(),
+            backend_config_template: "envs/{env}/terraform_state.tfvars".into(),
+            env_var: "ENV".into(),
+            required_env: vec!["ENV".into(), "AWS_PROFILE".into()],
+            backend: "terraform".into(),
+            terraform_bin: None,
+            required_version: None,
+        }
+    }
+}
+
+impl Config {
+    /// Load config, layering (in increasing precedence) built-in defaults,
+    /// a user-global `~/.config/tf/config.toml`, a project-local `tf.toml`,
+    /// and finally `TF_*` environment variable overrides.
+    pub fn load() -> Result<Self> {
+        let mut partial = PartialConfig::default();
+
+        if let Some(global) = Self::global_config_path() {
+            partial = partial.merge(PartialConfig::from_file(&global)?);
+        }
+
+        partial = partial.merge(PartialConfig::from_file(Path::new("tf.toml"))?);
+        partial = partial.merge(PartialConfig::from_env());
+
+        Ok(partial.resolve())
+    }
+
+    fn global_config_path() -> Option<PathBuf> {
+        let home = env::var("HOME").ok()?;
+        Some(Path::new(&home).join(".config/tf/config.toml"))
+    }
+
+    /// Render a `{env}`-templated path (e.g. `var_file_template`) for the given env.
+    pub fn render(template: &str, env: &str) -> PathBuf {
+        PathBuf::from(template.replace("{env}", env))
+    }
+}
+
+/// Mirrors `Config`, but every field is optional so layered `tf.toml` files
+/// only need to set the fields they want to override.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PartialConfig {
+    var_file_template: Option<String>,
+    backend_config_template: Option<String>,
+    env_var: Option<String>,
+    required_env: Option<Vec<String>>,
+    backend: Option<String>,
+    terraform_bin: Option<String>,
+    required_version: Option<String>,
+}
+
+impl PartialConfig {
+    fn from_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read {}", path.display()))?;
+
+        toml::from_str(&contents).with_context(|| format!("Unable to parse {}", path.display()))
+    }
+
+    fn from_env() -> Self {
+        Self {
+            var_file_template: env::var("TF_VAR_FILE_TEMPLATE").ok(),
+            backend_config_template: env::var("TF_BACKEND_CONFIG_TEMPLATE").ok(),
+            env_var: env::var("TF_ENV_VAR").ok(),
+            required_env: None,
+            backend: None,
+            terraform_bin: env::var("TF_BINARY").ok(),
+            required_version: env::var("TF_REQUIRED_VERSION").ok(),
+        }
+    }
+
+    /// Merge `other` over `self`, with fields set in `other` taking precedence.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            var_file_template: other.var_file_template.or(self.var_file_template),
+            backend_config_template: other
+                .backend_config_template
+                .or(self.backend_config_template),
+            env_var: other.env_var.or(self.env_var),
+            required_env: other.required_env.or(self.required_env),
+            backend: other.backend.or(self.backend),
+            terraform_bin: other.terraform_bin.or(self.terraform_bin),
+            required_version: other.required_version.or(self.required_version),
+        }
+    }
+
+    fn resolve(self) -> Config {
+        let defaults = Config::default();
+        Config {
+            var_file_template: self.var_file_template.unwrap_or(defaults.var_file_template),
+            backend_config_template: self
+                .backend_config_template
+                .unwrap_or(defaults.backend_config_template),
+            env_var: self.env_var.unwrap_or(defaults.env_var),
+            required_env: self.required_env.unwrap_or(defaults.required_env),
+            backend: self.backend.unwrap_or(defaults.backend),
+            terraform_bin: self.terraform_bin,
+            required_version: self.required_version,
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::{bail, Context, Result};
+use std::{env, fs, path::PathBuf};
+
+use crate::config::Config;
+
+/// One discovered environment directory and whether it has the files `tf` expects.
+struct EnvInfo {
+    name: String,
+    missing: Vec<String>,
+}
+
+impl EnvInfo {
+    fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Print every discovered environment, marking the active one (from
+/// `config.env_var`) and flagging any that are missing expected files.
+pub fn list(config: &Config) -> Result<()> {
+    let discovered = discover(config)?;
+
+    if discovered.is_empty() {
+        println!(
+            "No environments found under '{}/'.",
+            scan_root(config).display()
+        );
+        return Ok(());
+    }
+
+    let active = env::var(&config.env_var).ok();
+
+    for env in &discovered {
+        let marker = if active.as_deref() == Some(env.name.as_str()) {
+            "*"
+        } else {
+            " "
+        };
+
+        if env.is_complete() {
+            println!("{} {}", marker, env.name);
+        } else {
+            println!(
+                "{} {} (incomplete: missing {})",
+                marker,
+                env.name,
+                env.missing.join(", ")
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Reject an unknown environment name up front, before it reaches terraform.
+pub fn validate(config: &Config, name: &str) -> Result<()> {
+    let discovered = discover(config)?;
+
+    if discovered.iter().any(|env| env.name == name) {
+        return Ok(());
+    }
+
+    let available = discovered
+        .iter()
+        .map(|env| env.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    bail!(
+        "Unknown environment '{}'. Available environments: {}",
+        name,
+        if available.is_empty() {
+            format!("none found under {}/", scan_root(config).display())
+        } else {
+            available
+        }
+    );
+}
+
+/// The directory `discover` scans: the static part of `var_file_template`
+/// before its `{env}` placeholder, e.g. `envs/{env}/main.tfvars` -> `envs`.
+fn scan_root(config: &Config) -> PathBuf {
+    let prefix = config
+        .var_file_template
+        .split("{env}")
+        .next()
+        .unwrap_or("")
+        .trim_end_matches('/');
+
+    if prefix.is_empty() {
+        PathBuf::from(".")
+    } else {
+        PathBuf::from(prefix)
+    }
+}
+
+fn discover(config: &Config) -> Result<Vec<EnvInfo>> {
+    let root = scan_root(config);
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut discovered = Vec::new();
+    for entry in fs::read_dir(&root)
+        .with_context(|| format!("Unable to read {}/ directory", root.display()))?
+    {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let var_file = Config::render(&config.var_file_template, &name);
+        let backend_file = Config::render(&config.backend_config_template, &name);
+
+        let mut missing = Vec::new();
+        if !var_file.exists() {
+            missing.push(var_file.to_string_lossy().into_owned());
+        }
+        if !backend_file.exists() {
+            missing.push(backend_file.to_string_lossy().into_owned());
+        }
+
+        discovered.push(EnvInfo { name, missing });
+    }
+
+    discovered.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(discovered)
+}
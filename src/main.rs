@@ -1,21 +1,40 @@
+mod backend;
+mod config;
+mod envs;
+mod version;
+
 use anyhow::{Context, Result};
-use clap::{Args, Parser};
+use backend::Backend;
+use clap::{Args, Parser, Subcommand};
+use config::Config;
 use std::{
     env,
     fs::{self, File},
     io::{self, BufRead, BufReader},
-    path::Path,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
 #[derive(Parser)]
 #[clap(bin_name = "tf")]
+struct Cli {
+    #[clap(subcommand)]
+    command: Commands,
+
+    /// Override the configured backend (terraform, tofu, terragrunt).
+    #[clap(long, global = true)]
+    backend: Option<String>,
+}
+
+#[derive(Subcommand)]
 enum Commands {
     SetEnv(SetEnv),
     Plan(Plan),
     Apply(Apply),
     Destroy(Destroy),
     Init,
+    Workspace(Workspace),
+    Envs,
 }
 
 #[derive(Args)]
@@ -23,19 +42,22 @@ struct SetEnv {
     new_env: String,
 }
 
-fn set_env(opts: SetEnv) -> Result<()> {
+fn set_env(opts: SetEnv, config: &Config) -> Result<()> {
+    envs::validate(config, &opts.new_env)?;
+
     // Update .envrc
     let mut new_contents = String::new();
     {
         let envrc = File::open(".envrc").context("Cannot open envrc file")?;
         let reader = BufReader::new(envrc);
+        let export_prefix = format!("export {}", config.env_var);
 
         for line in LineIterator::new(b'\n', reader) {
             let line = line.unwrap();
             let line = std::str::from_utf8(&line).context("envrc appears to not be valid UTF-8")?;
-            if line.contains("export ENV") {
+            if line.contains(&export_prefix) {
                 // we can use \n, as we're expecting bash anyway.
-                new_contents.push_str(&format!("export ENV={}\n", &opts.new_env));
+                new_contents.push_str(&format!("export {}={}\n", config.env_var, &opts.new_env));
             } else {
                 new_contents.push_str(line);
             }
@@ -60,70 +82,152 @@ struct Apply {
     auto_approve: bool,
 }
 
-fn apply(opts: Apply) -> Result<()> {
-    check_env()?;
-    let env = env::var("ENV").context("ENV var not set")?;
-    let file = Path::new("envs").join(env).join("main.tfvars");
-    let file_str = file.to_string_lossy();
-    let mut args = vec!["destroy", "-var-file", &file_str];
-    if opts.auto_approve {
-        args.push("--auto-approve");
+fn apply(opts: Apply, config: &Config, backend: &dyn Backend) -> Result<()> {
+    check_env(config)?;
+    version::preflight(config, backend)?;
+    let env =
+        env::var(&config.env_var).with_context(|| format!("{} var not set", config.env_var))?;
+    select_workspace(&env, backend)?;
+    let var_file = Config::render(&config.var_file_template, &env);
+    let plan_file = plan_file_path(&env);
+
+    if is_fresh_plan(&plan_file, &var_file) {
+        if let Some(args) = backend.apply_plan_args(&plan_file.to_string_lossy()) {
+            run_terraform(backend, args)?;
+            return Ok(());
+        }
     }
 
-    run_terraform(args)?;
+    run_terraform(
+        backend,
+        backend.apply_args(&var_file.to_string_lossy(), opts.auto_approve),
+    )?;
 
     Ok(())
 }
 
 #[derive(Args)]
 struct Plan {
-    // additional_args: Option<Vec<String>>,
+    /// Where to save the plan, defaulting to `envs/<env>/<env>.tfplan`.
+    #[clap(long = "out")]
+    out: Option<String>,
 }
 
-fn plan() -> Result<()> {
-    check_env()?;
-    let env = env::var("ENV").expect("env not set");
-    let file = Path::new("envs").join(env).join("main.tfvars");
-
-    run_terraform(["plan", "-var-file", &file.to_string_lossy()])?;
+fn plan(opts: Plan, config: &Config, backend: &dyn Backend) -> Result<()> {
+    check_env(config)?;
+    version::preflight(config, backend)?;
+    let env =
+        env::var(&config.env_var).with_context(|| format!("{} var not set", config.env_var))?;
+    select_workspace(&env, backend)?;
+    let var_file = Config::render(&config.var_file_template, &env);
+    let out = opts
+        .out
+        .unwrap_or_else(|| plan_file_path(&env).to_string_lossy().into_owned());
+
+    run_terraform(
+        backend,
+        backend.plan_args(&var_file.to_string_lossy(), Some(&out)),
+    )?;
 
     Ok(())
 }
 
+/// Default location for a saved plan: `envs/<env>/<env>.tfplan`.
+fn plan_file_path(env: &str) -> PathBuf {
+    Path::new("envs").join(env).join(format!("{}.tfplan", env))
+}
+
+/// A saved plan is usable only if it exists and was written after the last
+/// change to the tfvars it was planned against.
+fn is_fresh_plan(plan_file: &Path, var_file: &Path) -> bool {
+    let (Ok(plan_meta), Ok(var_meta)) = (fs::metadata(plan_file), fs::metadata(var_file)) else {
+        return false;
+    };
+    let (Ok(plan_modified), Ok(var_modified)) = (plan_meta.modified(), var_meta.modified()) else {
+        return false;
+    };
+
+    plan_modified > var_modified
+}
+
 #[derive(Args)]
 struct Destroy {}
 
-fn destroy() -> Result<()> {
-    check_env()?;
-    let env = env::var("ENV")?;
-    let file = Path::new("envs").join(env).join("main.tfvars");
+fn destroy(config: &Config, backend: &dyn Backend) -> Result<()> {
+    check_env(config)?;
+    let env = env::var(&config.env_var)?;
+    select_workspace(&env, backend)?;
+    let file = Config::render(&config.var_file_template, &env);
 
-    run_terraform(["destroy", "-var-file", &file.to_string_lossy()])?;
+    run_terraform(backend, backend.destroy_args(&file.to_string_lossy()))?;
 
     Ok(())
 }
 
-fn init() -> Result<()> {
-    check_env()?;
-    let env = env::var("ENV")?;
-    let file = Path::new("envs").join(env).join("terraform_state.tfvars");
+#[derive(Args)]
+struct Workspace {
+    #[clap(subcommand)]
+    command: WorkspaceCommand,
+}
 
-    run_terraform(["init", "-backend-config", &file.to_string_lossy()])?;
+#[derive(Subcommand)]
+enum WorkspaceCommand {
+    /// List the Terraform workspaces known to the current backend.
+    List,
+    /// Switch to the named workspace, creating it if it doesn't exist yet.
+    Select { name: String },
+    /// Show the currently selected workspace.
+    Show,
+}
+
+fn workspace(opts: Workspace, backend: &dyn Backend) -> Result<()> {
+    match opts.command {
+        WorkspaceCommand::List => run_terraform(backend, backend.workspace_list_args())?,
+        WorkspaceCommand::Select { name } => select_workspace(&name, backend)?,
+        WorkspaceCommand::Show => run_terraform(backend, backend.workspace_show_args())?,
+    }
+
+    Ok(())
+}
+
+/// Select the Terraform workspace matching `env`, creating it with
+/// `terraform workspace new` first if it doesn't exist yet.
+fn select_workspace(env: &str, backend: &dyn Backend) -> Result<()> {
+    if run_terraform_status(backend, backend.workspace_select_args(env))?.success() {
+        return Ok(());
+    }
+
+    run_terraform(backend, backend.workspace_new_args(env)).context(format!(
+        "Unable to select or create the '{}' workspace",
+        env
+    ))?;
+
+    Ok(())
+}
+
+fn init(config: &Config, backend: &dyn Backend) -> Result<()> {
+    check_env(config)?;
+    version::preflight(config, backend)?;
+    let env = env::var(&config.env_var)?;
+    let file = Config::render(&config.backend_config_template, &env);
+
+    run_terraform(backend, backend.init_args(&file.to_string_lossy()))?;
 
     Ok(())
 }
 
 // Helpers
 
-fn check_env() -> Result<()> {
-    env::var("ENV")?;
-    env::var("AWS_PROFILE")?;
+fn check_env(config: &Config) -> Result<()> {
+    for var in &config.required_env {
+        env::var(var).with_context(|| format!("{} var not set", var))?;
+    }
 
     Ok(())
 }
 
-fn run_terraform<'a>(args: impl IntoIterator<Item = &'a str>) -> Result<()> {
-    Command::new("terraform")
+fn run_terraform(backend: &dyn Backend, args: Vec<String>) -> Result<()> {
+    Command::new(backend.binary())
         .args(args)
         .stdout(Stdio::inherit())
         .spawn()?
@@ -131,15 +235,37 @@ fn run_terraform<'a>(args: impl IntoIterator<Item = &'a str>) -> Result<()> {
 
     Ok(())
 }
+
+/// Like `run_terraform`, but swallows stdout/stderr and hands back the exit
+/// status instead of ignoring it, so callers can branch on success/failure.
+fn run_terraform_status(
+    backend: &dyn Backend,
+    args: Vec<String>,
+) -> Result<std::process::ExitStatus> {
+    let status = Command::new(backend.binary())
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?
+        .wait()?;
+
+    Ok(status)
+}
+
 fn main() -> Result<()> {
-    let args = Commands::parse();
-
-    match args {
-        Commands::SetEnv(a) => set_env(a)?,
-        Commands::Apply(a) => apply(a)?,
-        Commands::Plan(_) => plan()?,
-        Commands::Destroy(_) => destroy()?,
-        Commands::Init => init()?,
+    let cli = Cli::parse();
+    let config = Config::load()?;
+    let backend = backend::resolve(&config, cli.backend.as_deref())?;
+    let backend = backend.as_ref();
+
+    match cli.command {
+        Commands::SetEnv(a) => set_env(a, &config)?,
+        Commands::Apply(a) => apply(a, &config, backend)?,
+        Commands::Plan(a) => plan(a, &config, backend)?,
+        Commands::Destroy(_) => destroy(&config, backend)?,
+        Commands::Init => init(&config, backend)?,
+        Commands::Workspace(a) => workspace(a, backend)?,
+        Commands::Envs => envs::list(&config)?,
     }
 
     Ok(())
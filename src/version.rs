@@ -0,0 +1,147 @@
+use anyhow::{bail, Context, Result};
+use std::{fs, path::Path, process::Command};
+
+use crate::backend::Backend;
+use crate::config::Config;
+
+/// Run `<backend> version`, parse the installed `(major, minor, patch)`, and
+/// compare it against `required_version` (from config or `.terraform-version`).
+/// Skips silently when no constraint is configured, and warns rather than
+/// aborting when the backend binary can't be found at all.
+pub fn preflight(config: &Config, backend: &dyn Backend) -> Result<()> {
+    let Some(required) = required_version(config)? else {
+        return Ok(());
+    };
+
+    let installed = match installed_version(backend) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!(
+                "warning: could not determine {} version ({}); skipping version check",
+                backend.binary(),
+                e
+            );
+            return Ok(());
+        }
+    };
+
+    if !satisfies(installed, &required)? {
+        bail!(
+            "{} v{}.{}.{} does not satisfy required_version '{}'",
+            backend.binary(),
+            installed.0,
+            installed.1,
+            installed.2,
+            required
+        );
+    }
+
+    Ok(())
+}
+
+fn required_version(config: &Config) -> Result<Option<String>> {
+    if let Some(version) = &config.required_version {
+        return Ok(Some(version.clone()));
+    }
+
+    let path = Path::new(".terraform-version");
+    if path.exists() {
+        let contents = fs::read_to_string(path).context("Unable to read .terraform-version")?;
+        return Ok(Some(contents.trim().to_string()));
+    }
+
+    Ok(None)
+}
+
+fn installed_version(backend: &dyn Backend) -> Result<(u64, u64, u64)> {
+    let output = Command::new(backend.binary())
+        .arg("version")
+        .output()
+        .with_context(|| format!("Unable to run '{} version'", backend.binary()))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let first_line = stdout
+        .lines()
+        .next()
+        .context("No output from 'version' command")?;
+
+    parse_version_line(first_line)
+}
+
+/// Parses a line like `Terraform v1.5.7` or `Terraform v1.5.7-dev` by
+/// stripping the leading `<Name> v` and taking the dotted numeric prefix.
+fn parse_version_line(line: &str) -> Result<(u64, u64, u64)> {
+    let version_str = line
+        .split_once('v')
+        .map(|(_, rest)| rest)
+        .with_context(|| format!("Unrecognised version output: '{}'", line))?;
+
+    let numeric: String = version_str
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+
+    parse_dotted(&numeric)
+}
+
+fn parse_dotted(version: &str) -> Result<(u64, u64, u64)> {
+    let mut parts = version.split('.');
+    let major = parts
+        .next()
+        .context("Missing major version component")?
+        .parse()
+        .context("Major version is not numeric")?;
+    let minor = parts.next().unwrap_or("0").parse().unwrap_or(0);
+    let patch = parts.next().unwrap_or("0").parse().unwrap_or(0);
+
+    Ok((major, minor, patch))
+}
+
+/// Evaluates a comma-separated list of constraints (e.g. `>= 1.5.0, < 2.0.0`)
+/// against an installed version, requiring all clauses to hold.
+fn satisfies(installed: (u64, u64, u64), constraint: &str) -> Result<bool> {
+    for clause in constraint.split(',') {
+        if !satisfies_clause(installed, clause.trim())? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+fn satisfies_clause(installed: (u64, u64, u64), clause: &str) -> Result<bool> {
+    let (op, version_str) = if let Some(v) = clause.strip_prefix(">=") {
+        (">=", v)
+    } else if let Some(v) = clause.strip_prefix("<=") {
+        ("<=", v)
+    } else if let Some(v) = clause.strip_prefix("~>") {
+        ("~>", v)
+    } else if let Some(v) = clause.strip_prefix('>') {
+        (">", v)
+    } else if let Some(v) = clause.strip_prefix('<') {
+        ("<", v)
+    } else if let Some(v) = clause.strip_prefix('=') {
+        ("=", v)
+    } else {
+        ("=", clause)
+    };
+
+    let required = parse_dotted(version_str.trim())?;
+
+    Ok(match op {
+        "=" => installed == required,
+        ">=" => installed >= required,
+        ">" => installed > required,
+        "<=" => installed <= required,
+        "<" => installed < required,
+        "~>" => {
+            let upper = if version_str.trim().matches('.').count() >= 2 {
+                (required.0, required.1 + 1, 0)
+            } else {
+                (required.0 + 1, 0, 0)
+            };
+            installed >= required && installed < upper
+        }
+        _ => unreachable!(),
+    })
+}